@@ -1,8 +1,13 @@
 use crate::Project;
-use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, WeakModel};
+use anyhow::Context as _;
+use collections::HashMap;
+use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, WeakModel};
 use settings::Settings;
 use smol::channel::bounded;
-use std::path::{Path, PathBuf};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
 use terminal::{
     terminal_settings::{self, Shell, TerminalSettings, VenvSettingsContent},
     SpawnTask, TaskState, Terminal, TerminalBuilder,
@@ -28,12 +33,30 @@ impl Project {
             "creating terminals as a guest is not supported yet"
         );
 
+        let working_directory = working_directory
+            .or_else(|| self.default_terminal_working_directory(cx))
+            .unwrap_or_else(fallback_terminal_working_directory);
+
         let settings = TerminalSettings::get_global(cx);
         let python_settings = settings.detect_venv.clone();
         let (completion_tx, completion_rx) = bounded(1);
-        let mut env = settings.env.clone();
+        let mut env = settings
+            .env
+            .iter()
+            .map(|(key, value)| {
+                let value = value
+                    .resolve(terminal_settings::original_env_var(key))
+                    .with_context(|| format!("invalid `terminal.env` value for `{key}`"))?;
+                Ok((key.clone(), value))
+            })
+            .collect::<anyhow::Result<HashMap<String, OsString>>>()?;
         let (spawn_task, shell) = if let Some(spawn_task) = spawn_task {
-            env.extend(spawn_task.env);
+            env.extend(
+                spawn_task
+                    .env
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into())),
+            );
             (
                 Some(TaskState {
                     id: spawn_task.id,
@@ -47,11 +70,12 @@ impl Project {
                 },
             )
         } else {
+            validate_shell(&settings.shell, &env, &working_directory)?;
             (None, settings.shell.clone())
         };
 
         let terminal = TerminalBuilder::new(
-            working_directory.clone(),
+            Some(working_directory.clone()),
             spawn_task,
             shell,
             env,
@@ -85,10 +109,11 @@ impl Project {
             if let Some(python_settings) = &python_settings.as_option() {
                 let activate_command = Project::get_activate_command(python_settings);
                 let activate_script_path =
-                    self.find_activate_script_path(python_settings, working_directory);
+                    self.find_activate_script_path(python_settings, &working_directory);
                 self.activate_python_virtual_environment(
                     activate_command,
                     activate_script_path,
+                    python_settings.silent,
                     &terminal_handle,
                     cx,
                 );
@@ -102,12 +127,8 @@ impl Project {
     pub fn find_activate_script_path(
         &mut self,
         settings: &VenvSettingsContent,
-        working_directory: Option<PathBuf>,
+        working_directory: &Path,
     ) -> Option<PathBuf> {
-        // When we are unable to resolve the working directory, the terminal builder
-        // defaults to '/'. We should probably encode this directly somewhere, but for
-        // now, let's just hard code it here.
-        let working_directory = working_directory.unwrap_or_else(|| Path::new("/").to_path_buf());
         let activate_script_name = match settings.activate_script {
             terminal_settings::ActivateScript::Default => "activate",
             terminal_settings::ActivateScript::Csh => "activate.csh",
@@ -139,20 +160,53 @@ impl Project {
         &mut self,
         activate_command: &'static str,
         activate_script: Option<PathBuf>,
+        silent: bool,
         terminal_handle: &Model<Terminal>,
         cx: &mut ModelContext<Project>,
     ) {
         if let Some(activate_script) = activate_script {
-            // Paths are not strings so we need to jump through some hoops to format the command without `format!`
-            let mut command = Vec::from(activate_command.as_bytes());
+            let command = Self::activate_command_bytes(activate_command, &activate_script, silent);
+            terminal_handle.update(cx, |this, _| this.input_bytes(command));
+        }
+    }
+
+    fn activate_command_bytes(
+        activate_command: &'static str,
+        activate_script: &Path,
+        silent: bool,
+    ) -> Vec<u8> {
+        let mut command = Vec::new();
+        // A leading space keeps the command out of the history in shells
+        // configured with `HISTCONTROL=ignorespace` or `HIST_IGNORE_SPACE`.
+        if silent {
             command.push(b' ');
-            // Wrapping path in double quotes to catch spaces in folder name
-            command.extend_from_slice(b"\"");
-            command.extend_from_slice(activate_script.as_os_str().as_encoded_bytes());
-            command.extend_from_slice(b"\"");
-            command.push(b'\n');
+        }
+        // Paths are not strings so we need to jump through some hoops to format the command without `format!`
+        command.extend_from_slice(activate_command.as_bytes());
+        command.push(b' ');
+        // Wrapping path in double quotes to catch spaces in folder name
+        command.extend_from_slice(b"\"");
+        command.extend_from_slice(activate_script.as_os_str().as_encoded_bytes());
+        command.extend_from_slice(b"\"");
+        // `;` rather than `&&`, since nushell has no `&&`
+        if silent {
+            command.extend_from_slice(b"; clear");
+        }
+        command.push(b'\n');
+        command
+    }
 
-            terminal_handle.update(cx, |this, _| this.input_bytes(command));
+    /// The root of the first visible local worktree, or its parent directory
+    /// for a single-file worktree, used as the working directory of terminals
+    /// that weren't given one.
+    fn default_terminal_working_directory(&self, cx: &AppContext) -> Option<PathBuf> {
+        let worktree = self.visible_worktrees(cx).next()?;
+        let worktree = worktree.read(cx).as_local()?;
+        let abs_path = worktree.abs_path();
+        if worktree.root_entry()?.is_dir() {
+            Some(abs_path.to_path_buf())
+        } else {
+            abs_path.parent().map(Path::to_path_buf)
         }
     }
 
@@ -161,4 +215,149 @@ impl Project {
     }
 }
 
+/// Checks that the configured shell can be found before spawning it, since a
+/// missing program otherwise surfaces as an opaque error from the PTY.
+fn validate_shell(
+    shell: &Shell,
+    env: &HashMap<String, OsString>,
+    working_directory: &Path,
+) -> anyhow::Result<()> {
+    let program = match shell {
+        Shell::System => return Ok(()),
+        Shell::Program(program) | Shell::WithArguments { program, .. } => program,
+    };
+    // Variable names are case-insensitive on Windows, where it's usually `Path`
+    let paths = env
+        .iter()
+        .find(|(key, _)| terminal_settings::env_var_name_eq(key, "PATH"))
+        .map(|(_, value)| value.clone())
+        .or_else(|| std::env::var_os("PATH"));
+    which::which_in(program, paths, working_directory).map_err(|error| {
+        anyhow::anyhow!(
+            "Could not start the terminal shell `{program}`: {error}. \
+            Check that the `terminal.shell` setting points to an executable program."
+        )
+    })?;
+    Ok(())
+}
+
+/// Where terminals start when there's neither a requested working directory
+/// nor a worktree to default to.
+fn fallback_terminal_working_directory() -> PathBuf {
+    if cfg!(target_os = "windows") {
+        util::paths::HOME.clone()
+    } else {
+        PathBuf::from("/")
+    }
+}
+
 // TODO: Add a few tests for adding and removing terminal tabs
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_default_terminal_working_directory(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init("0.0.0", cx);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "dir": { "a.txt": "" },
+                "b.txt": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), [], cx).await;
+        project.read_with(cx, |project, cx| {
+            assert_eq!(project.default_terminal_working_directory(cx), None);
+        });
+
+        let project = Project::test(fs.clone(), ["/root/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, cx| {
+            assert_eq!(
+                project.default_terminal_working_directory(cx),
+                Some(PathBuf::from("/root/dir"))
+            );
+        });
+
+        let project = Project::test(fs, ["/root/b.txt".as_ref()], cx).await;
+        project.read_with(cx, |project, cx| {
+            assert_eq!(
+                project.default_terminal_working_directory(cx),
+                Some(PathBuf::from("/root"))
+            );
+        });
+    }
+
+    #[test]
+    fn test_validate_shell_missing_program() {
+        let error = validate_shell(
+            &Shell::Program("zed-nonexistent-shell".into()),
+            &HashMap::default(),
+            Path::new("/"),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("terminal.shell"), "{error}");
+
+        validate_shell(&Shell::System, &HashMap::default(), Path::new("/")).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validate_shell_uses_env_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = util::test::temp_tree(json!({ "my-shell": "" }));
+        let shell_path = dir.path().join("my-shell");
+        std::fs::set_permissions(&shell_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let shell = Shell::WithArguments {
+            program: "my-shell".into(),
+            args: vec!["--login".into()],
+        };
+        let mut env = HashMap::default();
+        env.insert("PATH".to_string(), dir.path().as_os_str().to_os_string());
+        validate_shell(&shell, &env, Path::new("/")).unwrap();
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_validate_shell_uses_env_path_case_insensitively() {
+        let dir = util::test::temp_tree(json!({ "my-shell.exe": "" }));
+
+        let mut env = HashMap::default();
+        env.insert("Path".to_string(), dir.path().as_os_str().to_os_string());
+        validate_shell(&Shell::Program("my-shell".into()), &env, Path::new("C:\\")).unwrap();
+    }
+
+    #[test]
+    fn test_activate_command_bytes() {
+        let script = Path::new("/some dir/.venv/bin/activate");
+
+        assert_eq!(
+            Project::activate_command_bytes("source", script, false),
+            b"source \"/some dir/.venv/bin/activate\"\n"
+        );
+        assert_eq!(
+            Project::activate_command_bytes("source", script, true),
+            b" source \"/some dir/.venv/bin/activate\"; clear\n"
+        );
+        assert_eq!(
+            Project::activate_command_bytes("overlay use", script, true),
+            b" overlay use \"/some dir/.venv/bin/activate\"; clear\n"
+        );
+    }
+}
@@ -45,6 +45,7 @@ use util::truncate_and_trailoff;
 
 use std::{
     cmp::{self, min},
+    ffi::OsString,
     fmt::Display,
     ops::{Deref, Index, RangeInclusive},
     path::PathBuf,
@@ -293,6 +294,24 @@ pub struct SpawnTask {
     pub env: HashMap<String, String>,
 }
 
+/// The program to run for `Shell::System`.
+///
+/// On Unix this is left to the PTY, which reads the user's login shell. Windows
+/// has no equivalent, so prefer PowerShell 7, then Windows PowerShell, and
+/// finally `cmd.exe`, which is always present.
+fn default_shell_program() -> Option<String> {
+    if cfg!(target_os = "windows") {
+        let paths = std::env::var_os("PATH").unwrap_or_default();
+        let program = ["pwsh.exe", "powershell.exe"]
+            .into_iter()
+            .find(|program| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+            .unwrap_or("cmd.exe");
+        Some(program.to_string())
+    } else {
+        None
+    }
+}
+
 // https://github.com/alacritty/alacritty/blob/cb3a79dbf6472740daca8440d5166c1d4af5029e/extra/man/alacritty.5.scd?plain=1#L207-L213
 const DEFAULT_SCROLL_HISTORY_LINES: usize = 10_000;
 const MAX_SCROLL_HISTORY_LINES: usize = 100_000;
@@ -307,7 +326,7 @@ impl TerminalBuilder {
         working_directory: Option<PathBuf>,
         task: Option<TaskState>,
         shell: Shell,
-        env: HashMap<String, String>,
+        env: HashMap<String, OsString>,
         blink_settings: Option<TerminalBlink>,
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
@@ -316,7 +335,8 @@ impl TerminalBuilder {
     ) -> Result<TerminalBuilder> {
         let pty_options = {
             let alac_shell = match shell.clone() {
-                Shell::System => None,
+                Shell::System => default_shell_program()
+                    .map(|program| alacritty_terminal::tty::Shell::new(program, Vec::new())),
                 Shell::Program(program) => {
                     Some(alacritty_terminal::tty::Shell::new(program, Vec::new()))
                 }
@@ -332,6 +352,9 @@ impl TerminalBuilder {
             }
         };
 
+        // Snapshot Zed's environment before any terminal writes to it
+        terminal_settings::original_env();
+
         // First, setup Alacritty's env
         setup_env();
 
@@ -1575,6 +1598,54 @@ mod tests {
         content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
     };
 
+    #[cfg(unix)]
+    #[gpui::test]
+    fn test_mouse_down_selection_by_click_count(cx: &mut gpui::TestAppContext) {
+        use crate::{
+            terminal_settings::{AlternateScroll, Shell},
+            InternalEvent, TerminalBuilder,
+        };
+        use alacritty_terminal::selection::SelectionType;
+        use collections::HashMap;
+        use gpui::{px, MouseButton, MouseDownEvent};
+
+        let window = cx.add_empty_window().handle();
+        let (completion_tx, _completion_rx) = smol::channel::bounded(1);
+        let mut terminal = TerminalBuilder::new(
+            None,
+            None,
+            Shell::Program("sh".into()),
+            HashMap::default(),
+            None,
+            AlternateScroll::On,
+            None,
+            window,
+            completion_tx,
+        )
+        .unwrap()
+        .terminal;
+
+        for (click_count, expected) in [
+            (1, SelectionType::Simple),
+            (2, SelectionType::Semantic),
+            (3, SelectionType::Lines),
+        ] {
+            let event = MouseDownEvent {
+                button: MouseButton::Left,
+                click_count,
+                ..Default::default()
+            };
+            terminal.mouse_down(&event, point(px(0.), px(0.)));
+
+            match terminal.events.pop_back() {
+                Some(InternalEvent::SetSelection(Some((selection, _)))) => {
+                    assert_eq!(selection.ty, expected, "click count {click_count}")
+                }
+                _ => panic!("click count {click_count} did not set a selection"),
+            }
+        }
+    }
+
     #[test]
     fn test_rgb_for_index() {
         //Test every possible value in the color cube
@@ -8,7 +8,12 @@ use schemars::{
 use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 use settings::SettingsJsonSchemaParams;
-use std::path::PathBuf;
+use std::{
+    env::JoinPathsError,
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+    sync::OnceLock,
+};
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -26,7 +31,7 @@ pub struct TerminalSettings {
     pub font_family: Option<String>,
     pub line_height: TerminalLineHeight,
     pub font_features: Option<FontFeatures>,
-    pub env: HashMap<String, String>,
+    pub env: HashMap<String, TerminalEnvValue>,
     pub blinking: TerminalBlink,
     pub alternate_scroll: AlternateScroll,
     pub option_as_meta: bool,
@@ -49,12 +54,18 @@ pub enum VenvSettings {
         /// in your project's settings, rather than globally.
         activate_script: Option<ActivateScript>,
         directories: Option<Vec<PathBuf>>,
+        /// Clear the screen once the activation command has run, so a fresh
+        /// terminal starts empty. The command is also prefixed with a space,
+        /// which keeps it out of the history in shells configured with
+        /// `HISTCONTROL=ignorespace` or `HIST_IGNORE_SPACE`.
+        silent: Option<bool>,
     },
 }
 
 pub struct VenvSettingsContent<'a> {
     pub activate_script: ActivateScript,
     pub directories: &'a [PathBuf],
+    pub silent: bool,
 }
 
 impl VenvSettings {
@@ -64,9 +75,11 @@ impl VenvSettings {
             VenvSettings::On {
                 activate_script,
                 directories,
+                silent,
             } => Some(VenvSettingsContent {
                 activate_script: activate_script.unwrap_or(ActivateScript::Default),
                 directories: directories.as_deref().unwrap_or(&[]),
+                silent: silent.unwrap_or(false),
             }),
         }
     }
@@ -108,10 +121,12 @@ pub struct TerminalSettingsContent {
     pub line_height: Option<TerminalLineHeight>,
     pub font_features: Option<FontFeatures>,
     /// Any key-value pairs added to this list will be added to the terminal's
-    /// environment. Use `:` to separate multiple values.
+    /// environment. Use `:` to separate multiple values. Use an object with
+    /// `prepend` and/or `append` lists to extend an inherited path list like
+    /// `PATH` instead of replacing it.
     ///
     /// Default: {}
-    pub env: Option<HashMap<String, String>>,
+    pub env: Option<HashMap<String, TerminalEnvValue>>,
     /// Sets the cursor blinking behavior in the terminal.
     ///
     /// Default: terminal_controlled
@@ -228,6 +243,82 @@ impl TerminalLineHeight {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(untagged)]
+pub enum TerminalEnvValue {
+    /// Replace the variable with this value.
+    Set(String),
+    /// Extend a path list variable, such as `PATH`, keeping the entries
+    /// it inherited from Zed's environment.
+    Paths(TerminalEnvPaths),
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TerminalEnvPaths {
+    /// Entries to add before the inherited ones.
+    #[serde(default)]
+    pub prepend: Vec<String>,
+    /// Entries to add after the inherited ones.
+    #[serde(default)]
+    pub append: Vec<String>,
+}
+
+impl TerminalEnvValue {
+    /// Computes the variable's value given the one it would otherwise inherit,
+    /// which should come from [`original_env_var`].
+    ///
+    /// Inherited entries are kept as they are, including empty ones, except
+    /// for those that are also being added. Fails if an added entry contains
+    /// the platform's path separator.
+    pub fn resolve(&self, inherited: Option<&OsStr>) -> Result<OsString, JoinPathsError> {
+        match self {
+            TerminalEnvValue::Set(value) => Ok(value.into()),
+            TerminalEnvValue::Paths(TerminalEnvPaths { prepend, append }) => {
+                let prepend = prepend.iter().map(PathBuf::from).collect::<Vec<_>>();
+                let append = append.iter().map(PathBuf::from).collect::<Vec<_>>();
+                let inherited = inherited
+                    // An empty variable has no entries, not one empty entry.
+                    .filter(|value| !value.is_empty())
+                    .into_iter()
+                    .flat_map(std::env::split_paths)
+                    .filter(|path| !prepend.contains(path) && !append.contains(path))
+                    .collect::<Vec<_>>();
+                std::env::join_paths(prepend.iter().chain(&inherited).chain(&append))
+            }
+        }
+    }
+}
+
+/// Zed's environment as it was before any terminal was created.
+///
+/// `TerminalBuilder` writes each terminal's variables into the process
+/// environment, so reading them back would pick up an earlier terminal's
+/// settings.
+pub(crate) fn original_env() -> &'static [(OsString, OsString)] {
+    static ORIGINAL_ENV: OnceLock<Vec<(OsString, OsString)>> = OnceLock::new();
+    ORIGINAL_ENV.get_or_init(|| std::env::vars_os().collect())
+}
+
+/// Looks up a variable in Zed's environment as it was before any terminal
+/// was created.
+pub fn original_env_var(name: &str) -> Option<&'static OsStr> {
+    original_env()
+        .iter()
+        .find(|(key, _)| key.to_str().map_or(false, |key| env_var_name_eq(key, name)))
+        .map(|(_, value)| value.as_os_str())
+}
+
+/// Compares environment variable names the way the platform does, ignoring
+/// case on Windows.
+pub fn env_var_name_eq(a: &str, b: &str) -> bool {
+    if cfg!(target_os = "windows") {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TerminalBlink {
@@ -243,7 +334,9 @@ pub enum TerminalBlink {
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Shell {
-    /// Use the system's default terminal configuration in /etc/passwd
+    /// Use the system's default terminal configuration in /etc/passwd.
+    /// On Windows, use PowerShell 7 if installed, then Windows PowerShell,
+    /// then `cmd.exe`.
     System,
     Program(String),
     WithArguments {
@@ -274,3 +367,134 @@ pub enum WorkingDirectory {
     /// this platform's home directory  (if it can be found).
     Always { directory: String },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_env_value() {
+        let env: HashMap<String, TerminalEnvValue> = serde_json::from_value(serde_json::json!({
+            "KEY": "value1:value2",
+            "PATH": { "prepend": ["node_modules/.bin"] },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            env["KEY"],
+            TerminalEnvValue::Set("value1:value2".to_string())
+        );
+        assert_eq!(
+            env["PATH"],
+            TerminalEnvValue::Paths(TerminalEnvPaths {
+                prepend: vec!["node_modules/.bin".to_string()],
+                append: Vec::new(),
+            })
+        );
+
+        // A misspelled field is an error rather than an empty path list.
+        assert!(
+            serde_json::from_value::<TerminalEnvValue>(serde_json::json!({
+                "prepand": ["node_modules/.bin"],
+            }))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_resolve_env_value() {
+        let inherited = std::env::join_paths(["/usr/local/bin", "/usr/bin"]).unwrap();
+
+        assert_eq!(
+            TerminalEnvValue::Set("value".into())
+                .resolve(Some(inherited.as_os_str()))
+                .unwrap(),
+            "value"
+        );
+
+        let paths = TerminalEnvValue::Paths(TerminalEnvPaths {
+            prepend: vec!["node_modules/.bin".into()],
+            append: vec!["/opt/tools".into()],
+        });
+        let expected = std::env::join_paths([
+            "node_modules/.bin",
+            "/usr/local/bin",
+            "/usr/bin",
+            "/opt/tools",
+        ])
+        .unwrap();
+        assert_eq!(
+            paths.resolve(Some(inherited.as_os_str())).unwrap(),
+            expected
+        );
+        // Resolving again, e.g. for a second terminal, doesn't repeat entries.
+        assert_eq!(paths.resolve(Some(expected.as_os_str())).unwrap(), expected);
+        assert_eq!(
+            paths.resolve(None).unwrap(),
+            std::env::join_paths(["node_modules/.bin", "/opt/tools"]).unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_env_value_keeps_non_utf8_entries() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let inherited = OsStr::from_bytes(b"/usr/bin:/opt/\xff/bin");
+        let paths = TerminalEnvValue::Paths(TerminalEnvPaths {
+            prepend: vec!["node_modules/.bin".into()],
+            append: Vec::new(),
+        });
+        assert_eq!(
+            paths.resolve(Some(inherited)).unwrap(),
+            OsStr::from_bytes(b"node_modules/.bin:/usr/bin:/opt/\xff/bin").to_os_string()
+        );
+    }
+
+    #[test]
+    fn test_resolve_env_value_after_settings_change() {
+        let inherited = std::env::join_paths(["/usr/bin", "/bin"]).unwrap();
+        let with_prepend = |entry: &str| {
+            TerminalEnvValue::Paths(TerminalEnvPaths {
+                prepend: vec![entry.into()],
+                append: Vec::new(),
+            })
+        };
+
+        assert_eq!(
+            with_prepend("/a")
+                .resolve(Some(inherited.as_os_str()))
+                .unwrap(),
+            std::env::join_paths(["/a", "/usr/bin", "/bin"]).unwrap()
+        );
+        // The new list replaces the old one rather than stacking on top of it,
+        // since both resolve against the original value.
+        assert_eq!(
+            with_prepend("/b")
+                .resolve(Some(inherited.as_os_str()))
+                .unwrap(),
+            std::env::join_paths(["/b", "/usr/bin", "/bin"]).unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_env_value_keeps_empty_entries() {
+        let paths = TerminalEnvValue::Paths(TerminalEnvPaths {
+            prepend: vec!["/a".into()],
+            append: Vec::new(),
+        });
+        assert_eq!(
+            paths.resolve(Some(OsStr::new("/usr/bin::/bin"))).unwrap(),
+            "/a:/usr/bin::/bin"
+        );
+        assert_eq!(paths.resolve(Some(OsStr::new(""))).unwrap(), "/a");
+    }
+
+    #[test]
+    fn test_original_env_ignores_later_changes() {
+        original_env();
+        std::env::set_var("ZED_TEST_ORIGINAL_ENV", "1");
+        assert_eq!(original_env_var("ZED_TEST_ORIGINAL_ENV"), None);
+    }
+}
@@ -24,6 +24,7 @@ use util::{ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     item::Item,
+    notifications::NotifyResultExt,
     pane,
     ui::IconName,
     DraggedTab, Pane, Workspace,
@@ -455,11 +456,13 @@ impl TerminalPanel {
                 };
 
                 let window = cx.window_handle();
-                if let Some(terminal) = workspace.project().update(cx, |project, cx| {
-                    project
-                        .create_terminal(working_directory, spawn_task, window, cx)
-                        .log_err()
-                }) {
+                if let Some(terminal) = workspace
+                    .project()
+                    .update(cx, |project, cx| {
+                        project.create_terminal(working_directory, spawn_task, window, cx)
+                    })
+                    .notify_err(workspace, cx)
+                {
                     let terminal = Box::new(cx.new_view(|cx| {
                         TerminalView::new(
                             terminal,
@@ -535,16 +538,19 @@ impl TerminalPanel {
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
     ) -> Option<()> {
-        let project = self
+        let new_terminal = self
             .workspace
-            .update(cx, |workspace, _| workspace.project().clone())
-            .ok()?;
-        let window = cx.window_handle();
-        let new_terminal = project.update(cx, |project, cx| {
-            project
-                .create_terminal(working_directory, Some(spawn_task), window, cx)
-                .log_err()
-        })?;
+            .update(cx, |workspace, cx| {
+                let window = cx.window_handle();
+                workspace
+                    .project()
+                    .update(cx, |project, cx| {
+                        project.create_terminal(working_directory, Some(spawn_task), window, cx)
+                    })
+                    .notify_err(workspace, cx)
+            })
+            .ok()
+            .flatten()?;
         terminal_to_replace.update(cx, |terminal_to_replace, cx| {
             terminal_to_replace.set_terminal(new_terminal, cx);
         });